@@ -0,0 +1,246 @@
+//! A minimal persistent hash-array-mapped trie (HAMT).
+//!
+//! This backs [`crate::ImBidiMap`]. Each level consumes 5 bits of the key's
+//! hash and dispatches into one of 32 children, so a full trie is at most
+//! 13 levels deep for a 64-bit hash. Unlike a textbook HAMT, branch nodes
+//! here are dense `[Node; 32]` arrays rather than bitmap-compressed sparse
+//! arrays — simpler to get right, at the cost of some wasted space in
+//! sparsely populated branches. Every node is reached through an `Rc`, so
+//! `insert`/`remove` only need to path-copy the spine from the root down to
+//! the touched leaf; every untouched sibling subtree is shared, not copied.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::rc::Rc;
+
+const BITS_PER_LEVEL: u32 = 5;
+const ARITY: usize = 1 << BITS_PER_LEVEL;
+const INDEX_MASK: u64 = (ARITY as u64) - 1;
+const MAX_DEPTH: u32 = 64 / BITS_PER_LEVEL;
+
+/// An owned key/value pair pulled out of a trie node.
+type Pair<K, V> = (Rc<K>, Rc<V>);
+
+fn hash_of<K: Hash>(key: &K) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn index_at(hash: u64, depth: u32) -> usize {
+    ((hash >> (depth * BITS_PER_LEVEL)) & INDEX_MASK) as usize
+}
+
+fn empty_branch<K, V>() -> [Node<K, V>; ARITY] {
+    std::array::from_fn(|_| Node::Empty)
+}
+
+enum Node<K, V> {
+    Empty,
+    Leaf(u64, Rc<K>, Rc<V>),
+    /// Entries whose hashes agree on every bit the trie can use (64 /
+    /// `BITS_PER_LEVEL` levels deep). Distinguished from each other by key
+    /// equality alone, since there are no hash bits left to branch on.
+    Collision(Rc<Vec<(Rc<K>, Rc<V>)>>),
+    Branch(Rc<[Node<K, V>; ARITY]>),
+}
+
+impl<K, V> Clone for Node<K, V> {
+    fn clone(&self) -> Self {
+        match self {
+            Node::Empty => Node::Empty,
+            Node::Leaf(hash, k, v) => Node::Leaf(*hash, k.clone(), v.clone()),
+            Node::Collision(bucket) => Node::Collision(bucket.clone()),
+            Node::Branch(children) => Node::Branch(children.clone()),
+        }
+    }
+}
+
+fn node_get<'a, K: Eq, V>(node: &'a Node<K, V>, hash: u64, key: &K, depth: u32) -> Option<&'a V> {
+    match node {
+        Node::Empty => None,
+        Node::Leaf(_, k, v) => if &**k == key { Some(v) } else { None },
+        Node::Collision(bucket) => bucket.iter().find(|(k, _)| &**k == key).map(|(_, v)| &**v),
+        Node::Branch(children) => node_get(&children[index_at(hash, depth)], hash, key, depth + 1),
+    }
+}
+
+/// Inserts `(key, value)`, returning the new node and the pair it displaced
+/// (if `key` was already present).
+fn node_insert<K: Eq, V>(
+    node: &Node<K, V>,
+    hash: u64,
+    key: Rc<K>,
+    value: Rc<V>,
+    depth: u32,
+) -> (Node<K, V>, Option<Pair<K, V>>) {
+    match node {
+        Node::Empty => (Node::Leaf(hash, key, value), None),
+        Node::Leaf(leaf_hash, leaf_key, leaf_value) => {
+            if **leaf_key == *key {
+                return (Node::Leaf(*leaf_hash, key, value), Some((leaf_key.clone(), leaf_value.clone())));
+            }
+            if depth >= MAX_DEPTH {
+                let bucket = vec![(leaf_key.clone(), leaf_value.clone()), (key, value)];
+                return (Node::Collision(Rc::new(bucket)), None);
+            }
+            let mut children = empty_branch();
+            children[index_at(*leaf_hash, depth)] = Node::Leaf(*leaf_hash, leaf_key.clone(), leaf_value.clone());
+            node_insert(&Node::Branch(Rc::new(children)), hash, key, value, depth)
+        }
+        Node::Collision(bucket) => {
+            let mut new_bucket = (**bucket).clone();
+            if let Some(slot) = new_bucket.iter_mut().find(|(k, _)| **k == *key) {
+                let old = (slot.0.clone(), slot.1.clone());
+                *slot = (key, value);
+                (Node::Collision(Rc::new(new_bucket)), Some(old))
+            } else {
+                new_bucket.push((key, value));
+                (Node::Collision(Rc::new(new_bucket)), None)
+            }
+        }
+        Node::Branch(children) => {
+            let idx = index_at(hash, depth);
+            let (new_child, old) = node_insert(&children[idx], hash, key, value, depth + 1);
+            let mut new_children = (**children).clone();
+            new_children[idx] = new_child;
+            (Node::Branch(Rc::new(new_children)), old)
+        }
+    }
+}
+
+/// Removes `key`, returning the new node and the pair it held (if any).
+fn node_remove<K: Eq, V>(node: &Node<K, V>, hash: u64, key: &K, depth: u32) -> (Node<K, V>, Option<Pair<K, V>>) {
+    match node {
+        Node::Empty => (Node::Empty, None),
+        Node::Leaf(_, leaf_key, leaf_value) => {
+            if &**leaf_key == key {
+                (Node::Empty, Some((leaf_key.clone(), leaf_value.clone())))
+            } else {
+                (node.clone(), None)
+            }
+        }
+        Node::Collision(bucket) => {
+            match bucket.iter().position(|(k, _)| &**k == key) {
+                None => (node.clone(), None),
+                Some(pos) => {
+                    let mut new_bucket = (**bucket).clone();
+                    let old = new_bucket.remove(pos);
+                    if new_bucket.len() == 1 {
+                        let (k, v) = new_bucket.into_iter().next().expect("just checked len == 1");
+                        (Node::Leaf(hash, k, v), Some(old))
+                    } else {
+                        (Node::Collision(Rc::new(new_bucket)), Some(old))
+                    }
+                }
+            }
+        }
+        Node::Branch(children) => {
+            let idx = index_at(hash, depth);
+            let (new_child, old) = node_remove(&children[idx], hash, key, depth + 1);
+            if old.is_none() {
+                return (node.clone(), None);
+            }
+            let mut new_children = (**children).clone();
+            new_children[idx] = new_child;
+            if new_children.iter().all(|child| matches!(child, Node::Empty)) {
+                (Node::Empty, old)
+            } else {
+                (Node::Branch(Rc::new(new_children)), old)
+            }
+        }
+    }
+}
+
+/// A persistent (immutable) hash trie: `insert`/`remove` return a new
+/// `Hamt` sharing all untouched structure with `self`, and `Clone` is O(1).
+pub(crate) struct Hamt<K, V> {
+    root: Node<K, V>,
+    len: usize,
+}
+
+impl<K, V> Clone for Hamt<K, V> {
+    fn clone(&self) -> Self {
+        Hamt { root: self.root.clone(), len: self.len }
+    }
+}
+
+impl<K: Eq + Hash, V> Hamt<K, V> {
+    pub(crate) fn new() -> Self {
+        Hamt { root: Node::Empty, len: 0 }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<&V> {
+        node_get(&self.root, hash_of(key), key, 0)
+    }
+
+    /// Returns a new trie with `(key, value)` inserted, plus the pair it
+    /// displaced, if `key` was already present.
+    pub(crate) fn inserted(&self, key: Rc<K>, value: Rc<V>) -> (Self, Option<Pair<K, V>>) {
+        let hash = hash_of(&key);
+        let (root, old) = node_insert(&self.root, hash, key, value, 0);
+        let len = if old.is_some() { self.len } else { self.len + 1 };
+        (Hamt { root, len }, old)
+    }
+
+    /// Returns a new trie with `key` removed, plus the pair it held, if
+    /// present.
+    pub(crate) fn removed(&self, key: &K) -> (Self, Option<Pair<K, V>>) {
+        let (root, old) = node_remove(&self.root, hash_of(key), key, 0);
+        let len = if old.is_some() { self.len - 1 } else { self.len };
+        (Hamt { root, len }, old)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_get_remove() {
+        let empty: Hamt<i32, &str> = Hamt::new();
+        let (one, old) = empty.inserted(Rc::new(1), Rc::new("a"));
+        assert_eq!(None, old);
+        assert_eq!(Some(&"a"), one.get(&1));
+        assert_eq!(0, empty.len());
+        assert_eq!(1, one.len());
+
+        let (two, old) = one.inserted(Rc::new(2), Rc::new("b"));
+        assert_eq!(None, old);
+        assert_eq!(Some(&"a"), two.get(&1));
+        assert_eq!(Some(&"b"), two.get(&2));
+        assert_eq!(Some(&"a"), one.get(&1));
+        assert_eq!(None, one.get(&2));
+
+        let (replaced, old) = two.inserted(Rc::new(1), Rc::new("c"));
+        assert_eq!(Some((Rc::new(1), Rc::new("a"))), old);
+        assert_eq!(Some(&"c"), replaced.get(&1));
+        assert_eq!(Some(&"a"), two.get(&1));
+
+        let (removed, old) = two.removed(&1);
+        assert_eq!(Some((Rc::new(1), Rc::new("a"))), old);
+        assert_eq!(None, removed.get(&1));
+        assert_eq!(Some(&"b"), removed.get(&2));
+        assert_eq!(1, removed.len());
+        assert_eq!(Some(&"a"), two.get(&1));
+    }
+
+    #[test]
+    fn scales_past_one_branch_level() {
+        let mut map = Hamt::new();
+        for i in 0..200 {
+            let (next, _) = map.inserted(Rc::new(i), Rc::new(i * 2));
+            map = next;
+        }
+
+        assert_eq!(200, map.len());
+        for i in 0..200 {
+            assert_eq!(Some(&(i * 2)), map.get(&i));
+        }
+    }
+}
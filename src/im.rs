@@ -0,0 +1,215 @@
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::hamt::Hamt;
+use crate::BidiMap;
+use crate::Overwritten;
+
+/// A persistent, immutable `BidiMap`, backed by a pair of
+/// [hash-array-mapped tries](crate::hamt) instead of `std::HashMap`.
+///
+/// Cloning an `ImBidiMap` is O(1) — both tries are reached through `Rc`, so
+/// a clone just bumps reference counts. [`inserted`](Self::inserted) and
+/// the other functional methods build on that: each returns a *new* map
+/// that shares all untouched trie structure with the map it was derived
+/// from, which is what makes snapshotting and branching (undo stacks,
+/// transactional edits) cheap. The [`BidiMap`] trait is still implemented
+/// in terms of these, for callers who just want a mutable handle.
+pub struct ImBidiMap<K1, K2> {
+    left_to_right: Hamt<K1, K2>,
+    right_to_left: Hamt<K2, K1>,
+}
+
+impl<K1, K2> Clone for ImBidiMap<K1, K2> {
+    fn clone(&self) -> Self {
+        Self {
+            left_to_right: self.left_to_right.clone(),
+            right_to_left: self.right_to_left.clone(),
+        }
+    }
+}
+
+impl<K1, K2> Default for ImBidiMap<K1, K2>
+where
+    K1: Eq + Hash + Clone,
+    K2: Eq + Hash + Clone,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K1, K2> ImBidiMap<K1, K2>
+where
+    K1: Eq + Hash + Clone,
+    K2: Eq + Hash + Clone,
+{
+    pub fn new() -> Self {
+        Self {
+            left_to_right: Hamt::new(),
+            right_to_left: Hamt::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.left_to_right.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The functional counterpart of [`BidiMap::insert`]: returns a new map
+    /// with `(k1, k2)` inserted, sharing all untouched structure with
+    /// `self`, along with whatever that insert displaced.
+    pub fn inserted(&self, k1: K1, k2: K2) -> (Self, Overwritten<K1, K2>) {
+        let same_pair = self.left_to_right.get(&k1).is_some_and(|existing| *existing == k2);
+
+        if same_pair {
+            let (without, removed) = self.remove_left_pair(&k1);
+            let (old_k1, old_k2) = removed.expect("checked above");
+            let inserted = without.insert_pair(k1, k2);
+            return (inserted, Overwritten::Pair(old_k1, old_k2));
+        }
+
+        let (without_left, left_evicted) = self.remove_left_pair(&k1);
+        let (without_either, right_evicted) = without_left.remove_right_pair(&k2);
+        let inserted = without_either.insert_pair(k1, k2);
+
+        let overwritten = match (left_evicted, right_evicted) {
+            (None, None) => Overwritten::Neither,
+            (Some((k1, k2)), None) => Overwritten::Left(k1, k2),
+            (None, Some((k1, k2))) => Overwritten::Right(k1, k2),
+            (Some(left), Some(right)) => Overwritten::Both(left, right),
+        };
+
+        (inserted, overwritten)
+    }
+
+    /// The functional counterpart of [`BidiMap::remove_by_left`].
+    pub fn removed_by_left(&self, k1: &K1) -> (Self, Option<(K1, K2)>) {
+        self.remove_left_pair(k1)
+    }
+
+    /// The functional counterpart of [`BidiMap::remove_by_right`].
+    pub fn removed_by_right(&self, k2: &K2) -> (Self, Option<(K1, K2)>) {
+        self.remove_right_pair(k2)
+    }
+
+    fn insert_pair(&self, k1: K1, k2: K2) -> Self {
+        let rc_k1 = Rc::new(k1);
+        let rc_k2 = Rc::new(k2);
+
+        let (left_to_right, _) = self.left_to_right.inserted(rc_k1.clone(), rc_k2.clone());
+        let (right_to_left, _) = self.right_to_left.inserted(rc_k2, rc_k1);
+
+        Self { left_to_right, right_to_left }
+    }
+
+    fn remove_left_pair(&self, k1: &K1) -> (Self, Option<(K1, K2)>) {
+        let (left_to_right, removed) = self.left_to_right.removed(k1);
+        match removed {
+            None => (self.clone(), None),
+            Some((rc_k1, rc_k2)) => {
+                let (right_to_left, _) = self.right_to_left.removed(&rc_k2);
+                let pair = ((*rc_k1).clone(), (*rc_k2).clone());
+                (Self { left_to_right, right_to_left }, Some(pair))
+            }
+        }
+    }
+
+    fn remove_right_pair(&self, k2: &K2) -> (Self, Option<(K1, K2)>) {
+        let (right_to_left, removed) = self.right_to_left.removed(k2);
+        match removed {
+            None => (self.clone(), None),
+            Some((rc_k2, rc_k1)) => {
+                let (left_to_right, _) = self.left_to_right.removed(&rc_k1);
+                let pair = ((*rc_k1).clone(), (*rc_k2).clone());
+                (Self { left_to_right, right_to_left }, Some(pair))
+            }
+        }
+    }
+}
+
+impl<'a, K1, K2> BidiMap<'a, K1, K2> for ImBidiMap<K1, K2>
+where
+    K1: Eq + Hash + Clone,
+    K2: Eq + Hash + Clone,
+{
+    fn insert(&mut self, k1: K1, k2: K2) -> Overwritten<K1, K2> {
+        let (inserted, overwritten) = self.inserted(k1, k2);
+        *self = inserted;
+        overwritten
+    }
+
+    fn get1(&self, k2: &K2) -> Option<&K1> {
+        self.right_to_left.get(k2)
+    }
+
+    fn get2(&self, k1: &K1) -> Option<&K2> {
+        self.left_to_right.get(k1)
+    }
+
+    fn remove_by_left(&mut self, k1: &K1) -> Option<(K1, K2)> {
+        let (remaining, removed) = self.removed_by_left(k1);
+        *self = remaining;
+        removed
+    }
+
+    fn remove_by_right(&mut self, k2: &K2) -> Option<(K1, K2)> {
+        let (remaining, removed) = self.removed_by_right(k2);
+        *self = remaining;
+        removed
+    }
+
+    fn len(&self) -> usize {
+        self.left_to_right.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inserted_shares_structure_and_leaves_originals_untouched() {
+        let empty = ImBidiMap::new();
+        let (one, overwritten) = empty.inserted(1, "a");
+        assert_eq!(Overwritten::Neither, overwritten);
+        assert_eq!(0, empty.len());
+        assert_eq!(1, one.len());
+
+        let (two, _) = one.inserted(2, "b");
+        assert_eq!(Some(&"a"), one.get2(&1));
+        assert_eq!(None, one.get2(&2));
+        assert_eq!(Some(&"a"), two.get2(&1));
+        assert_eq!(Some(&"b"), two.get2(&2));
+    }
+
+    #[test]
+    fn inserted_reports_overwritten() {
+        let map = ImBidiMap::new();
+        let (map, overwritten) = map.inserted(1, "a");
+        assert_eq!(Overwritten::Neither, overwritten);
+
+        let (map, overwritten) = map.inserted(1, "b");
+        assert_eq!(Overwritten::Left(1, "a"), overwritten);
+
+        let (map, overwritten) = map.inserted(2, "b");
+        assert_eq!(Overwritten::Right(1, "b"), overwritten);
+
+        let (map, _) = map.inserted(3, "c");
+        let (_, overwritten) = map.inserted(2, "c");
+        assert_eq!(Overwritten::Both((2, "b"), (3, "c")), overwritten);
+    }
+
+    #[test]
+    fn bidi_map_trait_mutates_in_place() {
+        let mut map = ImBidiMap::new();
+        assert_eq!(Overwritten::Neither, map.insert(1, "a"));
+        assert_eq!(Some(&"a"), map.get2(&1));
+        assert_eq!(Some((1, "a")), map.remove_by_left(&1));
+        assert_eq!(None, map.get2(&1));
+        assert_eq!(0, map.len());
+    }
+}
@@ -1,9 +1,25 @@
 use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
 use std::rc::Rc;
+use std::hash::BuildHasher;
 use std::hash::Hash;
 use std::ops::Deref;
 use std::ops::Index;
 use std::iter::Extend;
+use std::iter::FromIterator;
+
+mod ordered;
+pub use crate::ordered::OrderedBidiMap;
+
+mod hamt;
+mod im;
+pub use crate::im::ImBidiMap;
+
+mod with_values;
+pub use crate::with_values::BidiMapWithValues;
+
+#[cfg(feature = "serde")]
+mod serde_impl;
 
 pub trait MapLike<K, V> {
     fn get<'m>(&'m self, k: &K) -> Option<&'m V>;
@@ -45,6 +61,26 @@ impl<'a, K1, K2> Index<K2> for RightMap<'a, K1, K2> {
     }
 }
 
+/// What, if anything, was displaced by a call to [`BidiMap::insert`].
+///
+/// Inserting a pair can collide with up to two existing pairs: one sharing
+/// the new left key and one sharing the new right key. This reports exactly
+/// what was evicted so callers don't have to guess.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Overwritten<K1, K2> {
+    /// Nothing was displaced.
+    Neither,
+    /// The left key was already associated with a different right key.
+    Left(K1, K2),
+    /// The right key was already associated with a different left key.
+    Right(K1, K2),
+    /// The exact pair was already present.
+    Pair(K1, K2),
+    /// Both the left and right keys were already associated with other,
+    /// distinct pairs, and both were displaced.
+    Both((K1, K2), (K1, K2)),
+}
+
 pub trait BidiMap<'a, K1, K2> {
     fn as_map(&'a self) -> LeftMap<'a, K1, K2> where Self: Sized {
         LeftMap { bidi: self }
@@ -54,38 +90,71 @@ pub trait BidiMap<'a, K1, K2> {
         RightMap { bidi: self }
     }
 
-    fn insert(&mut self, k1: K1, k2: K2);
+    fn insert(&mut self, k1: K1, k2: K2) -> Overwritten<K1, K2>;
 
     fn get1(&self, k2: &K2) -> Option<&K1>;
     fn get2(&self, k1: &K1) -> Option<&K2>;
 
+    /// Removes the pair keyed by `k1` on the left side, if present, and
+    /// returns it.
+    fn remove_by_left(&mut self, k1: &K1) -> Option<(K1, K2)>;
+
+    /// Removes the pair keyed by `k2` on the right side, if present, and
+    /// returns it.
+    fn remove_by_right(&mut self, k2: &K2) -> Option<(K1, K2)>;
+
     fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
 
-pub struct HashBidiMap<K1, K2> {
-    left_to_right: HashMap<Rc<K1>, Rc<K2>>,
-    right_to_left: HashMap<Rc<K2>, Rc<K1>>,
+pub struct HashBidiMap<K1, K2, S1 = RandomState, S2 = RandomState> {
+    left_to_right: HashMap<Rc<K1>, Rc<K2>, S1>,
+    right_to_left: HashMap<Rc<K2>, Rc<K1>, S2>,
 }
 
-impl<'a, K1, K2> BidiMap<'a, K1, K2> for HashBidiMap<K1, K2>
+impl<'a, K1, K2, S1, S2> BidiMap<'a, K1, K2> for HashBidiMap<K1, K2, S1, S2>
 where
     K1: Eq + Hash,
     K2: Eq + Hash,
+    S1: BuildHasher,
+    S2: BuildHasher,
 {
-    fn insert(&mut self, k1: K1, k2: K2) {
-        if let Some(kk1) = self.right_to_left.get(&k2) {
-            self.left_to_right.remove(&*kk1);
-        }
+    fn insert(&mut self, k1: K1, k2: K2) -> Overwritten<K1, K2> {
+        let same_pair = self.left_to_right.get(&k1).is_some_and(|existing| **existing == k2);
 
-        if let Some(kk2) = self.left_to_right.get(&k1) {
-            self.right_to_left.remove(&*kk2);
-        }
+        let overwritten = if same_pair {
+            let (old_k1, old_k2) = self.evict_left(&k1).expect("checked above");
+            Overwritten::Pair(old_k1, old_k2)
+        } else {
+            let left = if self.left_to_right.contains_key(&k1) {
+                self.evict_left(&k1)
+            } else {
+                None
+            };
+            let right = if self.right_to_left.contains_key(&k2) {
+                self.evict_right(&k2)
+            } else {
+                None
+            };
+
+            match (left, right) {
+                (None, None) => Overwritten::Neither,
+                (Some((k1, k2)), None) => Overwritten::Left(k1, k2),
+                (None, Some((k1, k2))) => Overwritten::Right(k1, k2),
+                (Some(left), Some(right)) => Overwritten::Both(left, right),
+            }
+        };
 
         let a = Rc::new(k1);
         let b = Rc::new(k2);
 
         self.left_to_right.insert(a.clone(), b.clone());
         self.right_to_left.insert(b, a);
+
+        overwritten
     }
 
     fn get1(&self, k2: &K2) -> Option<&K1> {
@@ -96,13 +165,21 @@ where
         self.left_to_right.get(k1).map(Deref::deref)
     }
 
+    fn remove_by_left(&mut self, k1: &K1) -> Option<(K1, K2)> {
+        self.evict_left(k1)
+    }
+
+    fn remove_by_right(&mut self, k2: &K2) -> Option<(K1, K2)> {
+        self.evict_right(k2)
+    }
+
     fn len(&self) -> usize {
         self.left_to_right.len()
     }
 }
 
-impl<'a, K1, K2> Extend<(K1, K2)> for HashBidiMap<K1, K2>
-    where HashBidiMap<K1, K2> : BidiMap<'a, K1, K2>
+impl<'a, K1, K2, S1, S2> Extend<(K1, K2)> for HashBidiMap<K1, K2, S1, S2>
+    where HashBidiMap<K1, K2, S1, S2> : BidiMap<'a, K1, K2>
 {
     fn extend<T>(&mut self, iter: T)
     where T: IntoIterator<Item = (K1, K2)>
@@ -113,6 +190,51 @@ impl<'a, K1, K2> Extend<(K1, K2)> for HashBidiMap<K1, K2>
     }
 }
 
+/// Owned iterator over the pairs of a [`HashBidiMap`], produced by its
+/// [`IntoIterator`] impl.
+pub struct IntoIter<K1, K2> {
+    inner: std::collections::hash_map::IntoIter<Rc<K1>, Rc<K2>>,
+}
+
+impl<K1, K2> Iterator for IntoIter<K1, K2> {
+    type Item = (K1, K2);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k1, k2)| {
+            let k1 = Rc::try_unwrap(k1).ok().expect("dangling Rc<K1>");
+            let k2 = Rc::try_unwrap(k2).ok().expect("dangling Rc<K2>");
+            (k1, k2)
+        })
+    }
+}
+
+impl<K1, K2, S1, S2> IntoIterator for HashBidiMap<K1, K2, S1, S2> {
+    type Item = (K1, K2);
+    type IntoIter = IntoIter<K1, K2>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        // Dropping `right_to_left` here collapses every Rc's strong count
+        // back to one, so `IntoIter` can safely unwrap them one at a time.
+        IntoIter { inner: self.left_to_right.into_iter() }
+    }
+}
+
+impl<K1, K2, S1, S2> FromIterator<(K1, K2)> for HashBidiMap<K1, K2, S1, S2>
+where
+    K1: Eq + Hash,
+    K2: Eq + Hash,
+    S1: BuildHasher + Default,
+    S2: BuildHasher + Default,
+{
+    fn from_iter<T: IntoIterator<Item = (K1, K2)>>(iter: T) -> Self {
+        let mut map = Self::with_hashers(S1::default(), S2::default());
+        for (k1, k2) in iter {
+            map.insert(k1, k2);
+        }
+        map
+    }
+}
+
 impl<A, B> HashBidiMap<A, B>
 where
     A: Eq + Hash,
@@ -124,6 +246,74 @@ where
             right_to_left: HashMap::new(),
         }
     }
+
+    /// Creates an empty map with space reserved for at least `capacity`
+    /// pairs before reallocating.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            left_to_right: HashMap::with_capacity(capacity),
+            right_to_left: HashMap::with_capacity(capacity),
+        }
+    }
+}
+
+impl<A, B, S1, S2> HashBidiMap<A, B, S1, S2>
+where
+    A: Eq + Hash,
+    B: Eq + Hash,
+    S1: BuildHasher,
+    S2: BuildHasher,
+{
+    /// Creates an empty map that uses `s1`/`s2` to hash left/right keys
+    /// respectively, instead of the default `RandomState`.
+    pub fn with_hashers(s1: S1, s2: S2) -> Self {
+        Self {
+            left_to_right: HashMap::with_hasher(s1),
+            right_to_left: HashMap::with_hasher(s2),
+        }
+    }
+
+    /// The number of pairs the map can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.left_to_right.capacity()
+    }
+
+    /// Iterates over all pairs as `(&K1, &K2)`, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&A, &B)> {
+        self.left_to_right.iter().map(|(k1, k2)| (&**k1, &**k2))
+    }
+
+    /// Returns the `Rc` this map uses internally to key `k1`'s entry, so
+    /// other in-crate types (e.g. `BidiMapWithValues`) can key off the
+    /// very same allocation instead of storing a second copy of `K1`.
+    pub(crate) fn left_rc(&self, k1: &A) -> Option<Rc<A>> {
+        self.left_to_right.get_key_value(k1).map(|(rc, _)| rc.clone())
+    }
+
+    /// Removes the pair keyed by `k1` on the left side from both maps and
+    /// hands back the owned pair. Once the mirror entry is gone, both `Rc`s
+    /// drop to a strong count of one, so the `try_unwrap` calls succeed,
+    /// unless a clone of the left `Rc` obtained through
+    /// [`left_rc`](Self::left_rc) is still alive, in which case this panics.
+    fn evict_left(&mut self, k1: &A) -> Option<(A, B)> {
+        let (rc_k1, rc_k2) = self.left_to_right.remove_entry(k1)?;
+        self.right_to_left.remove(&*rc_k2);
+
+        let k1 = Rc::try_unwrap(rc_k1).ok().expect("dangling Rc<K1>");
+        let k2 = Rc::try_unwrap(rc_k2).ok().expect("dangling Rc<K2>");
+        Some((k1, k2))
+    }
+
+    /// Mirror of [`evict_left`](Self::evict_left), keyed by `k2` on the
+    /// right side.
+    fn evict_right(&mut self, k2: &B) -> Option<(A, B)> {
+        let (rc_k2, rc_k1) = self.right_to_left.remove_entry(k2)?;
+        self.left_to_right.remove(&*rc_k1);
+
+        let k1 = Rc::try_unwrap(rc_k1).ok().expect("dangling Rc<K1>");
+        let k2 = Rc::try_unwrap(rc_k2).ok().expect("dangling Rc<K2>");
+        Some((k1, k2))
+    }
 }
 
 #[cfg(test)]
@@ -158,6 +348,37 @@ mod tests {
         assert_eq!(2, map.len());
     }
 
+    #[test]
+    fn insert_reports_overwritten() {
+        let mut map = HashBidiMap::new();
+        assert_eq!(Overwritten::Neither, map.insert(1, "a"));
+        assert_eq!(Overwritten::Pair(1, "a"), map.insert(1, "a"));
+        assert_eq!(Overwritten::Left(1, "a"), map.insert(1, "b"));
+        assert_eq!(Overwritten::Right(1, "b"), map.insert(2, "b"));
+
+        map.insert(3, "c");
+        assert_eq!(Overwritten::Both((2, "b"), (3, "c")), map.insert(2, "c"));
+    }
+
+    #[test]
+    fn remove() {
+        let mut map = HashBidiMap::new();
+        map.insert(1, "a");
+        map.insert(2, "b");
+
+        assert_eq!(Some((1, "a")), map.remove_by_left(&1));
+        assert_eq!(None, map.get2(&1));
+        assert_eq!(None, map.get1(&"a"));
+        assert_eq!(1, map.len());
+
+        assert_eq!(Some((2, "b")), map.remove_by_right(&"b"));
+        assert_eq!(None, map.get1(&"b"));
+        assert_eq!(0, map.len());
+
+        assert_eq!(None, map.remove_by_left(&1));
+        assert_eq!(None, map.remove_by_right(&"b"));
+    }
+
     #[test]
     fn extend() {
         let mut map = HashBidiMap::new();
@@ -165,4 +386,49 @@ mod tests {
         assert_eq!(Some(&1), map.get1(&"a"));
         assert_eq!(Some(&"a"), map.get2(&1));
     }
+
+    #[test]
+    fn iter() {
+        let mut map = HashBidiMap::new();
+        map.insert(1, "a");
+        map.insert(2, "b");
+
+        let mut pairs: Vec<_> = map.iter().collect();
+        pairs.sort();
+        assert_eq!(vec![(&1, &"a"), (&2, &"b")], pairs);
+    }
+
+    #[test]
+    fn into_iter() {
+        let mut map = HashBidiMap::new();
+        map.insert(1, "a");
+        map.insert(2, "b");
+
+        let mut pairs: Vec<_> = map.into_iter().collect();
+        pairs.sort();
+        assert_eq!(vec![(1, "a"), (2, "b")], pairs);
+    }
+
+    #[test]
+    fn with_hashers_and_capacity() {
+        use std::collections::hash_map::RandomState;
+
+        let mut map = HashBidiMap::with_capacity(16);
+        assert!(map.capacity() >= 16);
+        map.insert(1, "a");
+        assert_eq!(Some(&"a"), map.get2(&1));
+
+        let mut map: HashBidiMap<_, _, RandomState, RandomState> =
+            HashBidiMap::with_hashers(RandomState::new(), RandomState::new());
+        map.insert(1, "a");
+        assert_eq!(Some(&1), map.get1(&"a"));
+    }
+
+    #[test]
+    fn from_iter() {
+        let map: HashBidiMap<_, _> = vec![(1, "a"), (2, "b")].into_iter().collect();
+        assert_eq!(Some(&"a"), map.get2(&1));
+        assert_eq!(Some(&1), map.get1(&"a"));
+        assert_eq!(2, map.len());
+    }
 }
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::BidiMap;
+use crate::Overwritten;
+
+/// A [`BidiMap`] that iterates pairs in the order they were first inserted.
+///
+/// Pairs live in a `Vec`, with `left_index`/`right_index` mapping each key
+/// to its slot. Overwriting or removing a pair removes its slot with
+/// `swap_remove`, so (as with `indexmap`'s `swap_remove`) an eviction or a
+/// `remove_by_left`/`remove_by_right` can move the previously-last pair
+/// into the freed slot, changing its position. Every insert appends the new
+/// pair at the end, including one that replaces or repeats an existing
+/// pair. Plain inserts that don't collide with anything always preserve
+/// order.
+pub struct OrderedBidiMap<K1, K2> {
+    entries: Vec<(Rc<K1>, Rc<K2>)>,
+    left_index: HashMap<Rc<K1>, usize>,
+    right_index: HashMap<Rc<K2>, usize>,
+}
+
+impl<'a, K1, K2> BidiMap<'a, K1, K2> for OrderedBidiMap<K1, K2>
+where
+    K1: Eq + Hash,
+    K2: Eq + Hash,
+{
+    fn insert(&mut self, k1: K1, k2: K2) -> Overwritten<K1, K2> {
+        let left_idx = self.left_index.get(&k1).copied();
+        let right_idx = self.right_index.get(&k2).copied();
+
+        let overwritten = match (left_idx, right_idx) {
+            (Some(li), Some(ri)) if li == ri => {
+                let (old_k1, old_k2) = self.evict_index(li);
+                Overwritten::Pair(old_k1, old_k2)
+            }
+            (Some(li), Some(ri)) => {
+                // Evict the larger index first: `swap_remove` only ever
+                // relocates the *current* last entry into the freed slot,
+                // and that entry sits at or beyond the larger index, so
+                // the pair at the smaller index is never moved before its
+                // own eviction.
+                let (first, second) = if li > ri { (li, ri) } else { (ri, li) };
+                let first_pair = self.evict_index(first);
+                let second_pair = self.evict_index(second);
+
+                if li > ri {
+                    Overwritten::Both(first_pair, second_pair)
+                } else {
+                    Overwritten::Both(second_pair, first_pair)
+                }
+            }
+            (Some(li), None) => {
+                let (old_k1, old_k2) = self.evict_index(li);
+                Overwritten::Left(old_k1, old_k2)
+            }
+            (None, Some(ri)) => {
+                let (old_k1, old_k2) = self.evict_index(ri);
+                Overwritten::Right(old_k1, old_k2)
+            }
+            (None, None) => Overwritten::Neither,
+        };
+
+        let idx = self.entries.len();
+        let a = Rc::new(k1);
+        let b = Rc::new(k2);
+
+        self.entries.push((a.clone(), b.clone()));
+        self.left_index.insert(a, idx);
+        self.right_index.insert(b, idx);
+
+        overwritten
+    }
+
+    fn get1(&self, k2: &K2) -> Option<&K1> {
+        let &idx = self.right_index.get(k2)?;
+        Some(&self.entries[idx].0)
+    }
+
+    fn get2(&self, k1: &K1) -> Option<&K2> {
+        let &idx = self.left_index.get(k1)?;
+        Some(&self.entries[idx].1)
+    }
+
+    fn remove_by_left(&mut self, k1: &K1) -> Option<(K1, K2)> {
+        let idx = *self.left_index.get(k1)?;
+        Some(self.evict_index(idx))
+    }
+
+    fn remove_by_right(&mut self, k2: &K2) -> Option<(K1, K2)> {
+        let idx = *self.right_index.get(k2)?;
+        Some(self.evict_index(idx))
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl<K1, K2> Default for OrderedBidiMap<K1, K2>
+where
+    K1: Eq + Hash,
+    K2: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K1, K2> OrderedBidiMap<K1, K2>
+where
+    K1: Eq + Hash,
+    K2: Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            entries: Vec::new(),
+            left_index: HashMap::new(),
+            right_index: HashMap::new(),
+        }
+    }
+
+    /// Iterates over all pairs in insertion order, as `(&K1, &K2)`.
+    pub fn iter(&self) -> impl Iterator<Item = (&K1, &K2)> {
+        self.entries.iter().map(|(k1, k2)| (&**k1, &**k2))
+    }
+
+    /// Removes the entry at `idx` from both index maps, fixing up the
+    /// index of whatever `swap_remove` moves into the freed slot.
+    fn evict_index(&mut self, idx: usize) -> (K1, K2) {
+        let (rc_k1, rc_k2) = self.entries.swap_remove(idx);
+        self.left_index.remove(&*rc_k1);
+        self.right_index.remove(&*rc_k2);
+
+        if idx < self.entries.len() {
+            let (moved_k1, moved_k2) = &self.entries[idx];
+            self.left_index.insert(moved_k1.clone(), idx);
+            self.right_index.insert(moved_k2.clone(), idx);
+        }
+
+        let k1 = Rc::try_unwrap(rc_k1).ok().expect("dangling Rc<K1>");
+        let k2 = Rc::try_unwrap(rc_k2).ok().expect("dangling Rc<K2>");
+        (k1, k2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preserves_insertion_order() {
+        let mut map = OrderedBidiMap::new();
+        map.insert(1, "a");
+        map.insert(2, "b");
+        map.insert(3, "c");
+
+        let pairs: Vec<_> = map.iter().collect();
+        assert_eq!(vec![(&1, &"a"), (&2, &"b"), (&3, &"c")], pairs);
+    }
+
+    #[test]
+    fn get_and_len() {
+        let mut map = OrderedBidiMap::new();
+        map.insert(1, "a");
+        map.insert(2, "b");
+
+        assert_eq!(Some(&"a"), map.get2(&1));
+        assert_eq!(Some(&1), map.get1(&"a"));
+        assert_eq!(2, map.len());
+    }
+
+    #[test]
+    fn insert_reports_overwritten() {
+        let mut map = OrderedBidiMap::new();
+        assert_eq!(Overwritten::Neither, map.insert(1, "a"));
+        assert_eq!(Overwritten::Pair(1, "a"), map.insert(1, "a"));
+        assert_eq!(Overwritten::Left(1, "a"), map.insert(1, "b"));
+        assert_eq!(Overwritten::Right(1, "b"), map.insert(2, "b"));
+
+        map.insert(3, "c");
+        assert_eq!(Overwritten::Both((2, "b"), (3, "c")), map.insert(2, "c"));
+    }
+
+    #[test]
+    fn remove_by_left_and_right() {
+        let mut map = OrderedBidiMap::new();
+        map.insert(1, "a");
+        map.insert(2, "b");
+        map.insert(3, "c");
+
+        assert_eq!(Some((2, "b")), map.remove_by_left(&2));
+        assert_eq!(None, map.get2(&2));
+        assert_eq!(2, map.len());
+
+        let pairs: Vec<_> = map.iter().collect();
+        assert_eq!(vec![(&1, &"a"), (&3, &"c")], pairs);
+
+        assert_eq!(Some((1, "a")), map.remove_by_right(&"a"));
+        assert_eq!(1, map.len());
+    }
+}
@@ -0,0 +1,119 @@
+//! `Serialize`/`Deserialize` support for [`HashBidiMap`], enabled by the
+//! `serde` feature.
+
+use std::fmt;
+use std::hash::BuildHasher;
+use std::hash::Hash;
+use std::marker::PhantomData;
+
+use serde::de::Deserialize;
+use serde::de::Deserializer;
+use serde::de::SeqAccess;
+use serde::de::Visitor;
+use serde::ser::Serialize;
+use serde::ser::SerializeSeq;
+use serde::ser::Serializer;
+
+use crate::BidiMap;
+use crate::HashBidiMap;
+
+impl<K1, K2, S1, S2> Serialize for HashBidiMap<K1, K2, S1, S2>
+where
+    K1: Serialize + Eq + Hash,
+    K2: Serialize + Eq + Hash,
+    S1: BuildHasher,
+    S2: BuildHasher,
+{
+    fn serialize<Se>(&self, serializer: Se) -> Result<Se::Ok, Se::Error>
+    where
+        Se: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for pair in self.iter() {
+            seq.serialize_element(&pair)?;
+        }
+        seq.end()
+    }
+}
+
+impl<'de, K1, K2, S1, S2> Deserialize<'de> for HashBidiMap<K1, K2, S1, S2>
+where
+    K1: Deserialize<'de> + Eq + Hash,
+    K2: Deserialize<'de> + Eq + Hash,
+    S1: BuildHasher + Default,
+    S2: BuildHasher + Default,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(PairSeqVisitor { marker: PhantomData })
+    }
+}
+
+struct PairSeqVisitor<K1, K2, S1, S2> {
+    marker: PhantomData<(K1, K2, S1, S2)>,
+}
+
+impl<'de, K1, K2, S1, S2> Visitor<'de> for PairSeqVisitor<K1, K2, S1, S2>
+where
+    K1: Deserialize<'de> + Eq + Hash,
+    K2: Deserialize<'de> + Eq + Hash,
+    S1: BuildHasher + Default,
+    S2: BuildHasher + Default,
+{
+    type Value = HashBidiMap<K1, K2, S1, S2>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a sequence of (K1, K2) pairs")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut map = HashBidiMap::with_hashers(S1::default(), S2::default());
+
+        // Replaying `insert` rebuilds both index maps from scratch, so a
+        // malformed sequence (e.g. duplicate keys) can never leave
+        // `left_to_right` and `right_to_left` disagreeing.
+        while let Some((k1, k2)) = seq.next_element()? {
+            map.insert(k1, k2);
+        }
+
+        Ok(map)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_json() {
+        let mut map: HashBidiMap<i32, String> = HashBidiMap::new();
+        map.insert(1, "a".to_string());
+        map.insert(2, "b".to_string());
+
+        let json = serde_json::to_string(&map).expect("serialize");
+        let restored: HashBidiMap<i32, String> = serde_json::from_str(&json).expect("deserialize");
+
+        assert_eq!(2, restored.len());
+        assert_eq!(Some(&"a".to_string()), restored.get2(&1));
+        assert_eq!(Some(&"b".to_string()), restored.get2(&2));
+    }
+
+    #[test]
+    fn deserializing_duplicate_left_key_keeps_both_sides_in_sync() {
+        let json = r#"[[1,"a"],[1,"b"]]"#;
+        let restored: HashBidiMap<i32, String> = serde_json::from_str(json).expect("deserialize");
+
+        // The second pair overwrites the first's right side via `insert`,
+        // so `left_to_right` and `right_to_left` must agree: only the new
+        // pair survives, and the stale right key is gone from both sides.
+        assert_eq!(1, restored.len());
+        assert_eq!(Some(&"b".to_string()), restored.get2(&1));
+        assert_eq!(Some(&1), restored.get1(&"b".to_string()));
+        assert_eq!(None, restored.get1(&"a".to_string()));
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::rc::Rc;
+
+use crate::BidiMap;
+use crate::HashBidiMap;
+use crate::Overwritten;
+
+/// Attaches an auxiliary value to each pair of a [`HashBidiMap`].
+///
+/// Useful when a left<->right association also needs a third payload (e.g.
+/// metadata alongside an id mapping), without callers having to juggle a
+/// `HashBidiMap` and a `HashMap` by hand and keep them consistent.
+pub struct BidiMapWithValues<K1, K2, V> {
+    bidi: HashBidiMap<K1, K2>,
+    values: HashMap<Rc<K1>, V>,
+}
+
+impl<K1, K2, V> Default for BidiMapWithValues<K1, K2, V>
+where
+    K1: Eq + Hash + Clone,
+    K2: Eq + Hash,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K1, K2, V> BidiMapWithValues<K1, K2, V>
+where
+    K1: Eq + Hash + Clone,
+    K2: Eq + Hash,
+{
+    pub fn new() -> Self {
+        Self {
+            bidi: HashBidiMap::new(),
+            values: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.bidi.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Inserts `(k1, k2)` into the underlying bidimap and associates `v`
+    /// with the pair.
+    ///
+    /// `HashBidiMap::insert` reclaims any pair it evicts via
+    /// `Rc::try_unwrap`, which only succeeds if that pair's left key has
+    /// no owners left besides the bidimap's own two internal maps. Since
+    /// `values` keys off that very same `Rc`, we have to drop our
+    /// reference to any left key this call is about to displace *before*
+    /// calling through — otherwise the unwrap would panic.
+    pub fn insert(&mut self, k1: K1, k2: K2, v: V) -> Overwritten<K1, K2> {
+        self.values.remove(&k1);
+        if let Some(other_k1) = self.bidi.get1(&k2) {
+            if *other_k1 != k1 {
+                let other_k1 = other_k1.clone();
+                self.values.remove(&other_k1);
+            }
+        }
+
+        let k1_for_lookup = k1.clone();
+        let overwritten = self.bidi.insert(k1, k2);
+
+        let rc_k1 = self.bidi.left_rc(&k1_for_lookup).expect("just inserted above");
+        self.values.insert(rc_k1, v);
+
+        overwritten
+    }
+
+    pub fn get_value_by_left(&self, k1: &K1) -> Option<&V> {
+        self.values.get(k1)
+    }
+
+    pub fn get_value_by_right(&self, k2: &K2) -> Option<&V> {
+        let k1 = self.bidi.get1(k2)?;
+        self.values.get(k1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_and_get_value() {
+        let mut map = BidiMapWithValues::new();
+        map.insert(1, "a", "meta-a");
+
+        assert_eq!(Some(&"meta-a"), map.get_value_by_left(&1));
+        assert_eq!(Some(&"meta-a"), map.get_value_by_right(&"a"));
+        assert_eq!(1, map.len());
+    }
+
+    #[test]
+    fn evicted_left_key_loses_its_value() {
+        let mut map = BidiMapWithValues::new();
+        map.insert(1, "a", "meta-a");
+        map.insert(2, "a", "meta-b");
+
+        // "a" now belongs to 2, so 1's value must be gone.
+        assert_eq!(None, map.get_value_by_left(&1));
+        assert_eq!(Some(&"meta-b"), map.get_value_by_left(&2));
+        assert_eq!(Some(&"meta-b"), map.get_value_by_right(&"a"));
+    }
+
+    #[test]
+    fn remapping_left_key_keeps_its_new_value() {
+        let mut map = BidiMapWithValues::new();
+        map.insert(1, "a", "meta-a");
+        map.insert(1, "b", "meta-b");
+
+        assert_eq!(Some(&"meta-b"), map.get_value_by_left(&1));
+        assert_eq!(None, map.get_value_by_right(&"a"));
+        assert_eq!(Some(&"meta-b"), map.get_value_by_right(&"b"));
+    }
+
+    #[test]
+    fn colliding_on_both_sides_drops_both_old_values() {
+        let mut map = BidiMapWithValues::new();
+        map.insert(1, "a", "meta-a");
+        map.insert(2, "b", "meta-b");
+        map.insert(1, "b", "meta-c");
+
+        // Key 1 keeps its slot but gets the new value; key 2 is fully
+        // evicted, so its value must be gone too.
+        assert_eq!(Some(&"meta-c"), map.get_value_by_left(&1));
+        assert_eq!(None, map.get_value_by_left(&2));
+        assert_eq!(Some(&"meta-c"), map.get_value_by_right(&"b"));
+        assert_eq!(None, map.get_value_by_right(&"a"));
+        assert_eq!(1, map.len());
+    }
+}